@@ -1,84 +1,366 @@
-#![cfg(test)]
 use semver::Version;
 use semver::VersionReq;
+use serde::Deserialize;
+use serde::Serialize;
 use std::fmt::Display;
 use std::fmt::Error;
 use url::Url;
 
-enum Target {
-    Crate(&'static str, VersionReq),
-    Rust(VersionReq),
+/// (De)serializes a `VersionReq` as its string form, e.g. `">= 1.0.3"`.
+mod version_req_str {
+    use semver::VersionReq;
+    use serde::de::Error as DeError;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+
+    pub fn serialize<S>(req: &VersionReq, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&req.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<VersionReq, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        VersionReq::parse(&raw).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serializes a `Url` as its string form.
+mod url_str {
+    use serde::de::Error as DeError;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+    use url::Url;
+
+    pub fn serialize<S>(url: &Url, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(url.as_str())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Url, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Url::parse(&raw).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serializes a `PartialVersion` as its string form, e.g. `"1.31"`.
+mod partial_version_str {
+    use crate::PartialVersion;
+    use serde::de::Error as DeError;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+
+    pub fn serialize<S>(version: &PartialVersion, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&version.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<PartialVersion, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        PartialVersion::parse(&raw).map_err(D::Error::custom)
+    }
+}
+
+/// A minimum-supported-rust-version, with minor/patch left unspecified when
+/// the record only cares about a major or major.minor toolchain.
+///
+/// Parses from `"1"`, `"1.31"`, or `"1.31.2"`. Absent components default to
+/// `0` when the version is turned into a requirement via [`to_caret_req`].
+///
+/// [`to_caret_req`]: PartialVersion::to_caret_req
+#[derive(Clone)]
+pub struct PartialVersion {
+    pub major: u64,
+    pub minor: Option<u64>,
+    pub patch: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum PartialVersionError {
+    Empty,
+    InvalidComponent(String),
+}
+
+impl Display for PartialVersionError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), Error> {
+        match self {
+            PartialVersionError::Empty => write!(formatter, "version string is empty"),
+            PartialVersionError::InvalidComponent(component) => {
+                write!(formatter, "invalid version component: {}", component)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PartialVersionError {}
+
+impl PartialVersion {
+    pub fn parse(input: &str) -> Result<Self, PartialVersionError> {
+        let mut parts = input.trim().splitn(3, '.');
+        let major = parts
+            .next()
+            .filter(|part| !part.is_empty())
+            .ok_or(PartialVersionError::Empty)?;
+        let major = major
+            .parse()
+            .map_err(|_| PartialVersionError::InvalidComponent(major.to_string()))?;
+        let minor = parts
+            .next()
+            .map(|part| {
+                part.parse()
+                    .map_err(|_| PartialVersionError::InvalidComponent(part.to_string()))
+            })
+            .transpose()?;
+        let patch = parts
+            .next()
+            .map(|part| {
+                part.parse()
+                    .map_err(|_| PartialVersionError::InvalidComponent(part.to_string()))
+            })
+            .transpose()?;
+        Ok(PartialVersion { major, minor, patch })
+    }
+
+    /// The Cargo-style caret requirement this MSRV implies: satisfied by any
+    /// toolchain at or above `major.minor.patch` and below the next major
+    /// version, the same rule Cargo applies to `^1.31` in a `Cargo.toml`.
+    pub fn to_caret_req(&self) -> VersionReq {
+        let minor = self.minor.unwrap_or(0);
+        let patch = self.patch.unwrap_or(0);
+        let next_major = self.major + 1;
+        VersionReq::parse(&format!(">={}.{}.{}, <{}.0.0", self.major, minor, patch, next_major))
+            .expect("caret requirement built from validated numeric components is always parseable")
+    }
+}
+
+impl Display for PartialVersion {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), Error> {
+        write!(formatter, "{}", self.major)?;
+        if let Some(minor) = self.minor {
+            write!(formatter, ".{}", minor)?;
+            if let Some(patch) = self.patch {
+                write!(formatter, ".{}", patch)?;
+            }
+        }
+        Ok(())
+    }
 }
-enum RefType {
-    Bug(Url),
-    PullRequest(Url),
-    Commit(Url),
+
+/// Strips any pre-release/build metadata from a candidate toolchain version,
+/// e.g. `1.70.0-nightly` becomes `1.70.0`, so MSRV and `Rust` comparisons
+/// only ever see the plain `major.minor.patch` triple.
+fn normalize_rustc_version(version: &Version) -> Version {
+    Version::new(version.major, version.minor, version.patch)
 }
-struct IncompatRecord {
-    target: Target,
-    conflicts: Target,
-    reason: Option<&'static str>,
-    references: Option<Vec<RefType>>,
+
+/// The boundary version a comparator draws, with absent components as `0`:
+/// its own version for `>=`/`<`, the next version past it for `>`/`<=`
+/// (`> 1.0.3` starts at `1.0.4`, `<= 1.31` ends before `1.32.0`).
+fn comparator_version(comparator: &semver::Comparator) -> Version {
+    let minor = comparator.minor.unwrap_or(0);
+    let patch = comparator.patch.unwrap_or(0);
+    if !matches!(comparator.op, semver::Op::Greater | semver::Op::LessEq) {
+        return Version::new(comparator.major, minor, patch);
+    }
+    match (comparator.minor, comparator.patch) {
+        (Some(_), Some(_)) => Version::new(comparator.major, minor, patch + 1),
+        (Some(_), None) => Version::new(comparator.major, minor + 1, 0),
+        _ => Version::new(comparator.major + 1, 0, 0),
+    }
+}
+
+/// The first version a `>=`/`>` comparator in `req` admits, if it has one —
+/// the floor a conflicting crate must stay below.
+fn lower_bound(req: &VersionReq) -> Option<Version> {
+    req.comparators
+        .iter()
+        .find(|comparator| matches!(comparator.op, semver::Op::GreaterEq | semver::Op::Greater))
+        .map(comparator_version)
+}
+
+/// The first version past a `<`/`<=` comparator in `req`, if it has one —
+/// the ceiling an affected crate must be raised to.
+fn upper_bound(req: &VersionReq) -> Option<Version> {
+    req.comparators
+        .iter()
+        .find(|comparator| matches!(comparator.op, semver::Op::Less | semver::Op::LessEq))
+        .map(comparator_version)
+}
+
+/// The minimal version change that takes a firing `IncompatRecord` out of
+/// effect: bump `bump_crate` to at least `to_at_least`, or pin it below
+/// `to_below`. Exactly one of the two is set.
+pub struct Resolution {
+    pub bump_crate: String,
+    pub to_at_least: Option<Version>,
+    pub to_below: Option<Version>,
+}
+
+/// A crate or rustc version that an `IncompatRecord` either targets or
+/// conflicts with.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Target {
+    Crate {
+        name: String,
+        #[serde(with = "version_req_str")]
+        req: VersionReq,
+    },
+    Rust {
+        #[serde(with = "version_req_str")]
+        req: VersionReq,
+    },
+    /// "Requires at least this toolchain", expressed as an MSRV rather than
+    /// a hand-inverted `< x.y` bound.
+    RustMsrv {
+        #[serde(with = "partial_version_str")]
+        msrv: PartialVersion,
+    },
+}
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RefType {
+    Bug {
+        #[serde(with = "url_str")]
+        url: Url,
+    },
+    PullRequest {
+        #[serde(with = "url_str")]
+        url: Url,
+    },
+    Commit {
+        #[serde(with = "url_str")]
+        url: Url,
+    },
+}
+#[derive(Serialize, Deserialize)]
+pub struct IncompatRecord {
+    pub reason: Option<String>,
+    pub target: Target,
+    pub conflicts: Target,
+    pub references: Option<Vec<RefType>>,
 }
 impl IncompatRecord {
-    fn affects_crate(&self, affected_crate: &str) -> bool {
-        if let Target::Crate(xcrate, _) = self.target {
-            xcrate == affected_crate
+    pub fn affects_crate(&self, affected_crate: &str) -> bool {
+        if let Target::Crate { name, .. } = &self.target {
+            name == affected_crate
         } else {
             false
         }
     }
-    fn affects(&self, affected_crate: &str, req: Version) -> bool {
-        if let Target::Crate(xcrate, crate_req) = &self.target {
-            xcrate == &affected_crate && crate_req.matches(&req)
+    pub fn affects(&self, affected_crate: &str, req: Version) -> bool {
+        if let Target::Crate { name, req: crate_req } = &self.target {
+            name == affected_crate && crate_req.matches(&req)
         } else {
             false
         }
     }
-    fn has_conflicts(&self, conflicting_crate: &str) -> bool {
-        if let Target::Crate(xcrate, _) = self.conflicts {
-            xcrate == conflicting_crate
+    pub fn has_conflicts(&self, conflicting_crate: &str) -> bool {
+        if let Target::Crate { name, .. } = &self.conflicts {
+            name == conflicting_crate
         } else {
             false
         }
     }
 
-    fn conflicts(&self, conflicting_crate: &str, req: Version) -> bool {
-        if let Target::Crate(xcrate, crate_req) = &self.conflicts {
-            xcrate == &conflicting_crate && crate_req.matches(&req)
+    pub fn conflicts(&self, conflicting_crate: &str, req: Version) -> bool {
+        if let Target::Crate { name, req: crate_req } = &self.conflicts {
+            name == conflicting_crate && crate_req.matches(&req)
         } else {
             false
         }
     }
 
-    fn has_rust_conflicts(&self) -> bool {
-        if let Target::Rust(_) = self.conflicts {
-            true
-        } else {
-            false
+    pub fn has_rust_conflicts(&self) -> bool {
+        matches!(self.conflicts, Target::Rust { .. } | Target::RustMsrv { .. })
+    }
+
+    pub fn rust_conflicts(&self, req: Version) -> bool {
+        let candidate = normalize_rustc_version(&req);
+        match &self.conflicts {
+            Target::Rust { req: rust_req } => rust_req.matches(&candidate),
+            Target::RustMsrv { msrv } => !msrv.to_caret_req().matches(&candidate),
+            _ => false,
         }
     }
 
-    fn rust_conflicts(&self, req: Version) -> bool {
-        if let Target::Rust(rust_req) = &self.conflicts {
-            rust_req.matches(&req)
+    /// Whether this record actually fires for a resolved package graph:
+    /// `target` must be present at a matching version, and either
+    /// `conflicts` is a crate also present at a matching version, or it's a
+    /// Rust conflict that `rustc` runs into (inside a `Rust` range, or below
+    /// a `RustMsrv`).
+    pub fn fires_for(&self, packages: &[(String, Version)], rustc: &Version) -> bool {
+        let target_present = packages
+            .iter()
+            .any(|(name, version)| self.affects(name, version.clone()));
+        if !target_present {
+            return false;
+        }
+        if self.has_rust_conflicts() {
+            self.rust_conflicts(rustc.clone())
         } else {
-            false
+            packages
+                .iter()
+                .any(|(name, version)| self.conflicts(name, version.clone()))
+        }
+    }
+
+    /// The minimal remediation for a firing record: prefer pinning the
+    /// `conflicts` crate below its lower bound, falling back to bumping the
+    /// `target` crate up to its upper bound. A `Rust` or `RustMsrv`
+    /// conflict has no crate to pin, so only the `target` side is tried.
+    /// Returns `None` when neither side has a bound this can invert.
+    pub fn resolution(&self) -> Option<Resolution> {
+        if let Target::Crate { name, req } = &self.conflicts {
+            if let Some(bound) = lower_bound(req) {
+                return Some(Resolution {
+                    bump_crate: name.clone(),
+                    to_at_least: None,
+                    to_below: Some(bound),
+                });
+            }
         }
+        if let Target::Crate { name, req } = &self.target {
+            if let Some(bound) = upper_bound(req) {
+                return Some(Resolution {
+                    bump_crate: name.clone(),
+                    to_at_least: Some(bound),
+                    to_below: None,
+                });
+            }
+        }
+        None
     }
 }
 
 impl Display for IncompatRecord {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), Error> {
-        write!(formatter, "{} with {}\n", &self.target, &self.conflicts)?;
-        if let Some(reason) = self.reason {
-            write!(formatter, "- {}\n", reason)?;
+        writeln!(formatter, "{} with {}", &self.target, &self.conflicts)?;
+        if let Some(reason) = &self.reason {
+            writeln!(formatter, "- {}", reason)?;
         }
         if let Some(references) = &self.references {
-            if references.len() > 0 {
-                write!(formatter, "References:\n")?;
+            if !references.is_empty() {
+                writeln!(formatter, "References:")?;
                 for reference in references {
-                    write!(formatter, "- {}\n", reference)?;
+                    writeln!(formatter, "- {}", reference)?;
                 }
             }
         }
@@ -89,8 +371,9 @@ impl Display for IncompatRecord {
 impl Display for Target {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), Error> {
         match &self {
-            Target::Rust(rust_req) => write!(formatter, "rust({})", rust_req),
-            Target::Crate(name, req) => write!(formatter, "crate({} {})", name, req),
+            Target::Rust { req } => write!(formatter, "rust({})", req),
+            Target::RustMsrv { msrv } => write!(formatter, "rust(>={})", msrv),
+            Target::Crate { name, req } => write!(formatter, "crate({} {})", name, req),
         }
     }
 }
@@ -98,13 +381,117 @@ impl Display for Target {
 impl Display for RefType {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), Error> {
         match &self {
-            RefType::Bug(url) => write!(formatter, "Bug: {}", url),
-            RefType::PullRequest(url) => write!(formatter, "Pull: {}", url),
-            RefType::Commit(url) => write!(formatter, "Commit: {}", url),
+            RefType::Bug { url } => write!(formatter, "Bug: {}", url),
+            RefType::PullRequest { url } => write!(formatter, "Pull: {}", url),
+            RefType::Commit { url } => write!(formatter, "Commit: {}", url),
         }
     }
 }
 
+/// A queryable collection of `IncompatRecord`s.
+///
+/// `IncompatDb` is the runtime entry point for consumers that want to ask
+/// "does this crate graph have any known incompatibilities" without
+/// hand-rolling the `affects`/`conflicts`/`rust_conflicts` predicates
+/// themselves.
+pub struct IncompatDb {
+    records: Vec<IncompatRecord>,
+}
+
+/// The on-disk shape of a serialized `IncompatDb`: a single `records` array,
+/// so a TOML dataset reads as a series of `[[records]]` tables.
+#[derive(Serialize, Deserialize)]
+struct Dataset {
+    records: Vec<IncompatRecord>,
+}
+
+/// A borrowing counterpart to `Dataset`, so writing a dataset back out
+/// doesn't require cloning every record.
+#[derive(Serialize)]
+struct DatasetRef<'a> {
+    records: &'a [IncompatRecord],
+}
+
+impl IncompatDb {
+    pub fn new(records: Vec<IncompatRecord>) -> Self {
+        IncompatDb { records }
+    }
+
+    pub fn records(&self) -> &[IncompatRecord] {
+        &self.records
+    }
+
+    /// All records whose `target` names `crate_name`, regardless of version.
+    pub fn records_affecting(&self, crate_name: &str) -> Vec<&IncompatRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.affects_crate(crate_name))
+            .collect()
+    }
+
+    /// Records where `crate_name` at `version` is the `target`, and the
+    /// `conflicts` side is some other crate.
+    pub fn conflicts_for(&self, crate_name: &str, version: &Version) -> Vec<&IncompatRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.affects(crate_name, version.clone()) && !record.has_rust_conflicts())
+            .collect()
+    }
+
+    /// Records whose `conflicts` side is a rustc bound that `rustc` runs
+    /// into (inside a `Rust` range, or below a `RustMsrv`), regardless of
+    /// which crate they target.
+    pub fn rust_incompatibilities(&self, rustc: &Version) -> Vec<&IncompatRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.has_rust_conflicts() && record.rust_conflicts(rustc.clone()))
+            .collect()
+    }
+
+    /// Scans a resolved package graph — typically the `name`/`version`
+    /// pairs parsed out of a `Cargo.lock` — against every record, returning
+    /// the ones that actually fire for that graph and the active `rustc`.
+    pub fn lockfile_conflicts(&self, packages: &[(String, Version)], rustc: &Version) -> Vec<&IncompatRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.fires_for(packages, rustc))
+            .collect()
+    }
+
+    /// Renders a set of firing records as an actionable report, one
+    /// `Display` block per hit, suitable for printing straight to the user.
+    pub fn format_report(hits: &[&IncompatRecord]) -> String {
+        hits.iter()
+            .map(|record| record.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Loads a dataset written as a TOML `records` array of tables.
+    pub fn from_toml_str(input: &str) -> Result<Self, toml::de::Error> {
+        let dataset: Dataset = toml::from_str(input)?;
+        Ok(IncompatDb::new(dataset.records))
+    }
+
+    /// Loads a dataset written as a JSON `records` array.
+    pub fn from_json_str(input: &str) -> Result<Self, serde_json::Error> {
+        let dataset: Dataset = serde_json::from_str(input)?;
+        Ok(IncompatDb::new(dataset.records))
+    }
+
+    /// Saves this database as a TOML `records` array of tables, the same
+    /// shape `from_toml_str` reads back.
+    pub fn to_toml_str(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(&DatasetRef { records: &self.records })
+    }
+
+    /// Saves this database as a JSON `records` array, the same shape
+    /// `from_json_str` reads back.
+    pub fn to_json_str(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&DatasetRef { records: &self.records })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::IncompatRecord;
@@ -116,23 +503,23 @@ mod tests {
 
     lazy_static! {
         static ref FAILURE_DERIVE: IncompatRecord = IncompatRecord {
-            target: Crate("failure_derive", VersionReq::parse("< 1.0.7").unwrap()),
-            conflicts: Crate("quote", VersionReq::parse(">= 1.0.3").unwrap()),
-            reason: Some("Broken by rename of quote::_rt to quote::_private in 1.0.3"),
+            target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+            conflicts: Crate { name: "quote".to_string(), req: VersionReq::parse(">= 1.0.3").unwrap() },
+            reason: Some("Broken by rename of quote::_rt to quote::_private in 1.0.3".to_string()),
             references: Some(vec![
-                Bug(Url::parse("https://github.com/withoutboats/failure_derive/issues/13").unwrap()),
-                Bug(Url::parse("https://github.com/rust-lang-nursery/failure/issues/342").unwrap()),
-                PullRequest(Url::parse("https://github.com/rust-lang-nursery/failure/pull/343").unwrap()),
-                PullRequest(Url::parse("https://github.com/rust-lang-nursery/failure/pull/345").unwrap()),
-                Commit(Url::parse("https://github.com/dtolnay/quote/commit/41543890aa76f4f8046fffac536b9445275aab26").unwrap()),
+                Bug { url: Url::parse("https://github.com/withoutboats/failure_derive/issues/13").unwrap() },
+                Bug { url: Url::parse("https://github.com/rust-lang-nursery/failure/issues/342").unwrap() },
+                PullRequest { url: Url::parse("https://github.com/rust-lang-nursery/failure/pull/343").unwrap() },
+                PullRequest { url: Url::parse("https://github.com/rust-lang-nursery/failure/pull/345").unwrap() },
+                Commit { url: Url::parse("https://github.com/dtolnay/quote/commit/41543890aa76f4f8046fffac536b9445275aab26").unwrap() },
             ])
         };
         static ref FAILURE_BADRUST: IncompatRecord = IncompatRecord {
-            target: Crate("failure_derive", VersionReq::parse("< 1.0.7").unwrap()),
-            conflicts: Rust(VersionReq::parse("< 1.31").unwrap()),
-            reason: Some("Documented minimum supported rust"),
+            target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+            conflicts: Rust { req: VersionReq::parse("< 1.31").unwrap() },
+            reason: Some("Documented minimum supported rust".to_string()),
             references: Some(vec![
-                Commit(Url::parse("https://github.com/rust-lang-nursery/failure/commit/996f919f1e1741b08673b15f893221694097cc9f").unwrap())
+                Commit { url: Url::parse("https://github.com/rust-lang-nursery/failure/commit/996f919f1e1741b08673b15f893221694097cc9f").unwrap() }
             ])
         };
     }
@@ -228,4 +615,335 @@ mod tests {
             println!("{}", *super::FAILURE_BADRUST);
         }
     }
+
+    mod incompat_db {
+        use crate::IncompatDb;
+        use crate::IncompatRecord;
+        use crate::Target::Crate;
+        use crate::Target::Rust;
+        use semver::Version;
+        use semver::VersionReq;
+
+        fn db() -> IncompatDb {
+            IncompatDb::new(vec![
+                IncompatRecord {
+                    target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                    conflicts: Crate { name: "quote".to_string(), req: VersionReq::parse(">= 1.0.3").unwrap() },
+                    reason: None,
+                    references: None,
+                },
+                IncompatRecord {
+                    target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                    conflicts: Rust { req: VersionReq::parse("< 1.31").unwrap() },
+                    reason: None,
+                    references: None,
+                },
+            ])
+        }
+
+        #[test]
+        fn test_records_affecting() {
+            assert_eq!(db().records_affecting("failure_derive").len(), 2);
+            assert_eq!(db().records_affecting("quote").len(), 0);
+        }
+
+        #[test]
+        fn test_conflicts_for() {
+            let db = db();
+            let hits = db.conflicts_for("failure_derive", &Version::parse("1.0.3").unwrap());
+            assert_eq!(hits.len(), 1);
+            assert!(hits[0].has_conflicts("quote"));
+        }
+
+        #[test]
+        fn test_rust_incompatibilities() {
+            let db = db();
+            let hits = db.rust_incompatibilities(&Version::parse("1.30.0").unwrap());
+            assert_eq!(hits.len(), 1);
+            assert!(hits[0].has_rust_conflicts());
+        }
+    }
+
+    mod serde_roundtrip {
+        use crate::IncompatDb;
+
+        const TOML_DATASET: &str = r#"
+            [[records]]
+            reason = "Broken by rename"
+
+            [records.target]
+            kind = "crate"
+            name = "failure_derive"
+            req = "< 1.0.7"
+
+            [records.conflicts]
+            kind = "crate"
+            name = "quote"
+            req = ">= 1.0.3"
+        "#;
+
+        const JSON_DATASET: &str = r#"{
+            "records": [
+                {
+                    "target": { "kind": "crate", "name": "failure_derive", "req": "< 1.0.7" },
+                    "conflicts": { "kind": "rust", "req": "< 1.31" },
+                    "reason": null,
+                    "references": null
+                }
+            ]
+        }"#;
+
+        #[test]
+        fn test_from_toml_str() {
+            let db = IncompatDb::from_toml_str(TOML_DATASET).unwrap();
+            assert_eq!(db.records_affecting("failure_derive").len(), 1);
+        }
+
+        #[test]
+        fn test_from_json_str() {
+            let db = IncompatDb::from_json_str(JSON_DATASET).unwrap();
+            assert!(db.records()[0].has_rust_conflicts());
+        }
+
+        #[test]
+        fn test_toml_round_trip() {
+            let db = IncompatDb::from_toml_str(TOML_DATASET).unwrap();
+            let written = db.to_toml_str().unwrap();
+            let reloaded = IncompatDb::from_toml_str(&written).unwrap();
+            assert_eq!(reloaded.records_affecting("failure_derive").len(), 1);
+        }
+
+        #[test]
+        fn test_json_round_trip() {
+            let db = IncompatDb::from_json_str(JSON_DATASET).unwrap();
+            let written = db.to_json_str().unwrap();
+            let reloaded = IncompatDb::from_json_str(&written).unwrap();
+            assert!(reloaded.records()[0].has_rust_conflicts());
+        }
+
+        const RUST_MSRV_TOML_DATASET: &str = r#"
+            [[records]]
+            reason = "Needs a newer toolchain"
+
+            [records.target]
+            kind = "crate"
+            name = "failure_derive"
+            req = "< 1.0.7"
+
+            [records.conflicts]
+            kind = "rust_msrv"
+            msrv = "1.31"
+        "#;
+
+        #[test]
+        fn test_from_toml_str_with_rust_msrv() {
+            let db = IncompatDb::from_toml_str(RUST_MSRV_TOML_DATASET).unwrap();
+            assert!(db.records()[0].has_rust_conflicts());
+        }
+
+        #[test]
+        fn test_rust_msrv_toml_round_trip() {
+            let db = IncompatDb::from_toml_str(RUST_MSRV_TOML_DATASET).unwrap();
+            let written = db.to_toml_str().unwrap();
+            let reloaded = IncompatDb::from_toml_str(&written).unwrap();
+            assert!(reloaded.records()[0].has_rust_conflicts());
+        }
+    }
+
+    mod partial_version {
+        use crate::PartialVersion;
+
+        #[test]
+        fn test_parse_major_only() {
+            let version = PartialVersion::parse("1").unwrap();
+            assert_eq!(version.major, 1);
+            assert_eq!(version.minor, None);
+            assert_eq!(version.patch, None);
+        }
+
+        #[test]
+        fn test_parse_major_minor() {
+            let version = PartialVersion::parse("1.31").unwrap();
+            assert_eq!(version.major, 1);
+            assert_eq!(version.minor, Some(31));
+            assert_eq!(version.patch, None);
+        }
+
+        #[test]
+        fn test_parse_full() {
+            let version = PartialVersion::parse("1.31.2").unwrap();
+            assert_eq!(version.major, 1);
+            assert_eq!(version.minor, Some(31));
+            assert_eq!(version.patch, Some(2));
+        }
+
+        #[test]
+        fn test_parse_invalid() {
+            assert!(PartialVersion::parse("").is_err());
+            assert!(PartialVersion::parse("one.two").is_err());
+        }
+
+        #[test]
+        fn test_to_caret_req() {
+            let req = PartialVersion::parse("1.31").unwrap().to_caret_req();
+            assert!(req.matches(&semver::Version::parse("1.31.0").unwrap()));
+            assert!(req.matches(&semver::Version::parse("1.99.0").unwrap()));
+            assert!(!req.matches(&semver::Version::parse("1.30.9").unwrap()));
+            assert!(!req.matches(&semver::Version::parse("2.0.0").unwrap()));
+        }
+
+        #[test]
+        fn test_to_caret_req_major_only() {
+            let req = PartialVersion::parse("1").unwrap().to_caret_req();
+            assert!(req.matches(&semver::Version::parse("1.0.0").unwrap()));
+            assert!(!req.matches(&semver::Version::parse("2.0.0").unwrap()));
+        }
+    }
+
+    mod rust_msrv {
+        use crate::IncompatRecord;
+        use crate::PartialVersion;
+        use crate::Target::Crate;
+        use crate::Target::RustMsrv;
+        use semver::Version;
+        use semver::VersionReq;
+
+        fn record() -> IncompatRecord {
+            IncompatRecord {
+                target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                conflicts: RustMsrv { msrv: PartialVersion::parse("1.31").unwrap() },
+                reason: None,
+                references: None,
+            }
+        }
+
+        #[test]
+        fn test_has_rust_conflicts() {
+            assert!(record().has_rust_conflicts());
+        }
+
+        #[test]
+        fn test_conflicts_below_msrv() {
+            assert!(record().rust_conflicts(Version::parse("1.30.0").unwrap()));
+        }
+
+        #[test]
+        fn test_no_conflict_at_or_above_msrv() {
+            assert!(!record().rust_conflicts(Version::parse("1.31.0").unwrap()));
+            assert!(!record().rust_conflicts(Version::parse("1.40.0").unwrap()));
+        }
+
+        #[test]
+        fn test_strips_prerelease_before_matching() {
+            assert!(!record().rust_conflicts(Version::parse("1.31.0-nightly").unwrap()));
+            assert!(record().rust_conflicts(Version::parse("1.30.0-nightly").unwrap()));
+        }
+    }
+
+    mod lockfile_conflicts {
+        use crate::IncompatDb;
+        use crate::IncompatRecord;
+        use crate::Target::Crate;
+        use crate::Target::RustMsrv;
+        use semver::Version;
+        use semver::VersionReq;
+
+        fn db() -> IncompatDb {
+            IncompatDb::new(vec![
+                IncompatRecord {
+                    target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                    conflicts: Crate { name: "quote".to_string(), req: VersionReq::parse(">= 1.0.3").unwrap() },
+                    reason: None,
+                    references: None,
+                },
+                IncompatRecord {
+                    target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                    conflicts: RustMsrv { msrv: crate::PartialVersion::parse("1.31").unwrap() },
+                    reason: None,
+                    references: None,
+                },
+            ])
+        }
+
+        fn packages(pairs: &[(&str, &str)]) -> Vec<(String, Version)> {
+            pairs
+                .iter()
+                .map(|(name, version)| (name.to_string(), Version::parse(version).unwrap()))
+                .collect()
+        }
+
+        #[test]
+        fn test_fires_on_crate_conflict() {
+            let db = db();
+            let packages = packages(&[("failure_derive", "1.0.3"), ("quote", "1.0.4")]);
+            let hits = db.lockfile_conflicts(&packages, &Version::parse("1.40.0").unwrap());
+            assert_eq!(hits.len(), 1);
+            assert!(hits[0].has_conflicts("quote"));
+        }
+
+        #[test]
+        fn test_fires_on_rust_conflict() {
+            let db = db();
+            let packages = packages(&[("failure_derive", "1.0.3")]);
+            let hits = db.lockfile_conflicts(&packages, &Version::parse("1.30.0").unwrap());
+            assert_eq!(hits.len(), 1);
+            assert!(hits[0].has_rust_conflicts());
+        }
+
+        #[test]
+        fn test_no_conflict_when_target_absent() {
+            let db = db();
+            let packages = packages(&[("quote", "1.0.4")]);
+            let hits = db.lockfile_conflicts(&packages, &Version::parse("1.30.0").unwrap());
+            assert!(hits.is_empty());
+        }
+
+        #[test]
+        fn test_format_report_includes_reason() {
+            let records = [IncompatRecord {
+                target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                conflicts: Crate { name: "quote".to_string(), req: VersionReq::parse(">= 1.0.3").unwrap() },
+                reason: Some("Broken by rename".to_string()),
+                references: None,
+            }];
+            let report = IncompatDb::format_report(&records.iter().collect::<Vec<_>>());
+            assert!(report.contains("Broken by rename"));
+        }
+    }
+
+    mod resolution {
+        use crate::IncompatRecord;
+        use crate::PartialVersion;
+        use crate::Target::Crate;
+        use crate::Target::RustMsrv;
+        use semver::VersionReq;
+
+        #[test]
+        fn test_prefers_pinning_the_conflicting_crate() {
+            let record = IncompatRecord {
+                target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                conflicts: Crate { name: "quote".to_string(), req: VersionReq::parse(">= 1.0.3").unwrap() },
+                reason: None,
+                references: None,
+            };
+            let resolution = record.resolution().unwrap();
+            assert_eq!(resolution.bump_crate, "quote");
+            assert_eq!(resolution.to_below.unwrap().to_string(), "1.0.3");
+            assert!(resolution.to_at_least.is_none());
+        }
+
+        #[test]
+        fn test_falls_back_to_bumping_the_affected_crate() {
+            let record = IncompatRecord {
+                target: Crate { name: "failure_derive".to_string(), req: VersionReq::parse("< 1.0.7").unwrap() },
+                conflicts: RustMsrv { msrv: PartialVersion::parse("1.31").unwrap() },
+                reason: None,
+                references: None,
+            };
+            let resolution = record.resolution().unwrap();
+            assert_eq!(resolution.bump_crate, "failure_derive");
+            assert_eq!(resolution.to_at_least.unwrap().to_string(), "1.0.7");
+            assert!(resolution.to_below.is_none());
+        }
+    }
 }